@@ -0,0 +1,43 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum GridError {
+    Io(io::Error),
+    InvalidChar(char),
+    WidthMismatch(usize, usize),
+    EmptyGrid,
+    AdjacentCells,
+    LaneUnbalanced,
+    SameLanes,
+    Unsolvable,
+    AmbiguousPuzzle,
+    OddDimension,
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(fmt, "I/O error: {}", err),
+            Self::InvalidChar(c) => write!(fmt, "invalid character '{}'", c),
+            Self::WidthMismatch(expected, got) => {
+                write!(fmt, "line width mismatch: expected {}, got {}", expected, got)
+            }
+            Self::EmptyGrid => write!(fmt, "grid is empty"),
+            Self::AdjacentCells => write!(fmt, "more than 2 adjacent cells with the same value"),
+            Self::LaneUnbalanced => write!(fmt, "line or column is not balanced"),
+            Self::SameLanes => write!(fmt, "two lines or columns are identical"),
+            Self::Unsolvable => write!(fmt, "grid has no solution"),
+            Self::AmbiguousPuzzle => write!(fmt, "grid has multiple solutions"),
+            Self::OddDimension => write!(fmt, "grid dimensions must be even"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+impl From<io::Error> for GridError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
@@ -1,76 +1,357 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::hash::Hash;
 use std::ops;
 
 use crate::error::GridError;
 
-#[derive(Debug)]
-pub struct Grid {
-    cells: Vec<Vec<Option<Cell>>>,
+// A value a cell can hold, paired with its opposite. The grid rules (no more
+// than 2 consecutive equal values, balanced lines, no two identical lines)
+// only ever reason about a value and its complement, so any two-symbol
+// domain can plug in here; `Cell` is the default binary one.
+pub trait Symbol: Copy + Eq + Hash + fmt::Display {
+    fn complement(self) -> Self;
+    fn from_char(c: char) -> Option<Self>
+    where
+        Self: Sized;
+    fn values() -> [Self; 2]
+    where
+        Self: Sized;
+}
+
+#[derive(Debug, Clone)]
+pub struct Grid<T: Symbol = Cell> {
+    cells: Vec<Option<T>>,
     width: usize,
     height: usize,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Cell {
     Zero,
     One,
 }
 
-impl Grid {
-    pub fn parse<I, S, E>(lines: I) -> Result<Grid, GridError>
+impl Symbol for Cell {
+    fn complement(self) -> Self {
+        !self
+    }
+
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '0' => Some(Self::Zero),
+            '1' => Some(Self::One),
+            _ => None,
+        }
+    }
+
+    fn values() -> [Self; 2] {
+        [Self::Zero, Self::One]
+    }
+}
+
+/// Outcome of exhaustively searching a grid for solutions.
+#[derive(Debug)]
+pub enum Solutions<T: Symbol = Cell> {
+    None,
+    Unique(Grid<T>),
+    Multiple,
+}
+
+impl<T: Symbol> Grid<T> {
+    pub fn parse<I, S, E>(lines: I) -> Result<Grid<T>, GridError>
     where
         I: Iterator<Item = Result<S, E>>,
         S: AsRef<str>,
         GridError: From<E>,
     {
-        let mut cells: Vec<Vec<_>> = Vec::new();
+        let mut cells = Vec::new();
+        let mut width = None;
 
         for line in lines {
-            let mut vec = Vec::new();
+            let mut row = Vec::new();
 
             for c in line?.as_ref().chars() {
                 match c {
                     ' ' | '\t' => {}
-                    '0' => vec.push(Some(Cell::Zero)),
-                    '1' => vec.push(Some(Cell::One)),
-                    '-' => vec.push(None),
-                    _ => {
-                        return Err(GridError::InvalidChar(c));
-                    }
+                    '-' => row.push(None),
+                    _ => match T::from_char(c) {
+                        Some(symbol) => row.push(Some(symbol)),
+                        None => {
+                            return Err(GridError::InvalidChar(c));
+                        }
+                    },
                 };
             }
 
-            if !vec.is_empty() {
-                if let Some(prev) = cells.last() {
-                    if vec.len() != prev.len() {
-                        return Err(GridError::WidthMismatch(prev.len(), vec.len()));
+            if !row.is_empty() {
+                match width {
+                    Some(width) if width != row.len() => {
+                        return Err(GridError::WidthMismatch(width, row.len()));
                     }
+                    Some(_) => {}
+                    None => width = Some(row.len()),
                 }
 
-                cells.push(vec);
+                cells.append(&mut row);
+            }
+        }
+
+        let width = width.ok_or(GridError::EmptyGrid)?;
+        let height = cells.len() / width;
+
+        Ok(Grid { cells, height, width })
+    }
+
+    // Treats a blank line as a delimiter between puzzles, so a file holding
+    // several puzzles back to back is parsed as one `Grid` each rather than
+    // merged into one (or rejected on a width mismatch between them).
+    pub fn parse_many<I, S, E>(lines: I) -> Result<Vec<Grid<T>>, GridError>
+    where
+        I: Iterator<Item = Result<S, E>>,
+        S: AsRef<str>,
+        GridError: From<E>,
+    {
+        let mut grids = Vec::new();
+        let mut current: Vec<S> = Vec::new();
+
+        for line in lines {
+            let line = line?;
+
+            if line.as_ref().chars().all(|c| c == ' ' || c == '\t') {
+                if !current.is_empty() {
+                    grids.push(Self::parse(current.drain(..).map(Ok::<S, E>))?);
+                }
+            } else {
+                current.push(line);
             }
         }
 
-        if cells.is_empty() {
+        if !current.is_empty() {
+            grids.push(Self::parse(current.into_iter().map(Ok::<S, E>))?);
+        }
+
+        if grids.is_empty() {
             return Err(GridError::EmptyGrid);
         }
 
-        let height = cells.len();
-        let width = cells[0].len();
+        Ok(grids)
+    }
 
-        Ok(Grid { cells, height, width })
+    // Builds a puzzle with a unique solution: fill the grid completely with
+    // a randomized backtracking search, then strip cells in randomized order
+    // as long as the remainder still has a unique solution.
+    pub fn generate(width: usize, height: usize, seed: u64) -> Result<Grid<T>, GridError> {
+        if width == 0 || height == 0 {
+            return Err(GridError::EmptyGrid);
+        }
+
+        if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+            return Err(GridError::OddDimension);
+        }
+
+        let mut rng = Rng::new(seed);
+        let empty = Grid {
+            cells: vec![None; width * height],
+            width,
+            height,
+        };
+
+        let mut grid = Self::random_fill(empty, &mut rng).ok_or(GridError::Unsolvable)?;
+
+        let mut positions: Vec<(usize, usize)> =
+            (0..height).flat_map(|i| (0..width).map(move |j| (i, j))).collect();
+        rng.shuffle(&mut positions);
+
+        for idx in positions {
+            let removed = grid[idx].take();
+
+            if !matches!(grid.solutions()?, Solutions::Unique(_)) {
+                grid[idx] = removed;
+            }
+        }
+
+        Ok(grid)
+    }
+
+    // Propagates at every node, not just once upfront, so each guess prunes
+    // the remaining search instead of leaving it to branch on every empty
+    // cell independently.
+    fn random_fill(mut grid: Grid<T>, rng: &mut Rng) -> Option<Grid<T>> {
+        grid.propagate();
+
+        if grid.is_valid().is_err() {
+            return None;
+        }
+
+        if grid.is_filled() {
+            return Some(grid);
+        }
+
+        let (i, j) = grid.first_empty().expect("grid is not filled");
+        let mut values = T::values();
+        rng.shuffle(&mut values);
+
+        for value in values {
+            let mut candidate = grid.clone();
+            candidate[(i, j)] = Some(value);
+
+            if let Some(solution) = Self::random_fill(candidate, rng) {
+                return Some(solution);
+            }
+        }
+
+        None
     }
 
     pub fn solve(&mut self) -> Result<(), GridError> {
-        // TODO
+        match self.solutions()? {
+            Solutions::Unique(solution) => {
+                *self = solution;
+                Ok(())
+            }
+            Solutions::None => Err(GridError::Unsolvable),
+            Solutions::Multiple => Err(GridError::AmbiguousPuzzle),
+        }
+    }
 
+    /// Exhaustively searches the grid for solutions, stopping as soon as a
+    /// second one is found since a well-formed puzzle must have exactly one.
+    pub fn solutions(&self) -> Result<Solutions<T>, GridError> {
         self.is_valid()?;
 
-        if self.is_filled() {
-            Ok(())
+        let mut found = Vec::new();
+        Self::collect_solutions(self.clone(), &mut found);
+
+        match found.len() {
+            0 => Ok(Solutions::None),
+            1 => Ok(Solutions::Unique(found.pop().expect("one solution was found"))),
+            _ => Ok(Solutions::Multiple),
+        }
+    }
+
+    // Propagates at every node, not just once upfront: re-deriving forced
+    // cells after each guess collapses most of the branching that a plain
+    // guess-and-check backtrack would otherwise explore, which is what makes
+    // `generate`'s repeated uniqueness checks tractable past trivial sizes.
+    fn collect_solutions(mut grid: Grid<T>, found: &mut Vec<Grid<T>>) {
+        if found.len() >= 2 {
+            return;
+        }
+
+        grid.propagate();
+
+        if grid.is_valid().is_err() {
+            return;
+        }
+
+        if grid.is_filled() {
+            found.push(grid);
+            return;
+        }
+
+        let (i, j) = grid.first_empty().expect("grid is not filled");
+
+        for value in T::values() {
+            let mut candidate = grid.clone();
+            candidate[(i, j)] = Some(value);
+
+            Self::collect_solutions(candidate, found);
+
+            if found.len() >= 2 {
+                return;
+            }
+        }
+    }
+
+    fn first_empty(&self) -> Option<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|i| (0..self.width).map(move |j| (i, j)))
+            .find(|&idx| self[idx].is_none())
+    }
+
+    // Runs the deductive propagation rules over every row and column until
+    // no cell changes.
+    fn propagate(&mut self) {
+        loop {
+            let mut changed = false;
+
+            for i in 0..self.height {
+                changed |= self.propagate_lane((0..self.width).map(|j| (i, j)).collect());
+            }
+
+            for j in 0..self.width {
+                changed |= self.propagate_lane((0..self.height).map(|i| (i, j)).collect());
+            }
+
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    fn propagate_lane(&mut self, indices: Vec<(usize, usize)>) -> bool {
+        let len = indices.len();
+        let half = len / 2;
+        let mut changed = false;
+
+        // Two adjacent cells holding the same value force their neighbours,
+        // on either side of the pair, to hold the opposite value.
+        for w in 0..len.saturating_sub(1) {
+            let (a, b) = (indices[w], indices[w + 1]);
+
+            if let (Some(x), Some(y)) = (self[a], self[b]) {
+                if x == y {
+                    if w > 0 {
+                        changed |= self.fill(indices[w - 1], x.complement());
+                    }
+                    if w + 2 < len {
+                        changed |= self.fill(indices[w + 2], x.complement());
+                    }
+                }
+            }
+        }
+
+        // A gap surrounded by two equal cells must hold the opposite value.
+        for w in 0..len.saturating_sub(2) {
+            let (a, gap, b) = (indices[w], indices[w + 1], indices[w + 2]);
+
+            if let (Some(x), Some(y)) = (self[a], self[b]) {
+                if x == y {
+                    changed |= self.fill(gap, x.complement());
+                }
+            }
+        }
+
+        // A line that already holds half of one value forces every
+        // remaining empty cell to hold the other value.
+        let mut counts: HashMap<T, usize> = HashMap::new();
+
+        for &idx in &indices {
+            if let Some(x) = self[idx] {
+                *counts.entry(x).or_insert(0) += 1;
+            }
+        }
+
+        if let Some((&symbol, _)) = counts.iter().find(|&(_, &count)| count == half) {
+            let complement = symbol.complement();
+
+            for &idx in &indices {
+                if self[idx].is_none() {
+                    changed |= self.fill(idx, complement);
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn fill(&mut self, idx: (usize, usize), value: T) -> bool {
+        if self[idx].is_none() {
+            self[idx] = Some(value);
+            true
         } else {
-            unimplemented!()
+            false
         }
     }
 
@@ -113,12 +394,10 @@ impl Grid {
         I: Iterator<Item = ((usize, usize), (usize, usize), (usize, usize))>,
     {
         for (idx0, idx1, idx2) in indices {
-            match (&self[idx0], &self[idx1], &self[idx2]) {
-                (Some(Cell::Zero), Some(Cell::Zero), Some(Cell::Zero))
-                | (Some(Cell::One), Some(Cell::One), Some(Cell::One)) => {
+            if let (Some(x), Some(y), Some(z)) = (self[idx0], self[idx1], self[idx2]) {
+                if x == y && y == z {
                     return Err(GridError::AdjacentCells);
                 }
-                _ => {}
             }
         }
 
@@ -129,10 +408,10 @@ impl Grid {
     where
         I: Iterator<Item = (usize, usize)>,
     {
-        let mut balance = HashMap::new();
+        let mut balance: HashMap<T, usize> = HashMap::new();
 
         for idx in indices {
-            match &self[idx] {
+            match self[idx] {
                 Some(x) => {
                     balance.entry(x).and_modify(|count| *count += 1).or_insert(1);
                 }
@@ -142,7 +421,10 @@ impl Grid {
             }
         }
 
-        if balance[&Cell::Zero] != balance[&Cell::One] {
+        if balance
+            .iter()
+            .any(|(x, count)| *count != *balance.get(&x.complement()).unwrap_or(&0))
+        {
             return Err(GridError::LaneUnbalanced);
         }
 
@@ -154,8 +436,8 @@ impl Grid {
         I: Iterator<Item = ((usize, usize), (usize, usize))>,
     {
         for (idx0, idx1) in indices {
-            match (&self[idx0], &self[idx1]) {
-                (Some(Cell::Zero), Some(Cell::Zero)) | (Some(Cell::One), Some(Cell::One)) => {}
+            match (self[idx0], self[idx1]) {
+                (Some(x), Some(y)) if x == y => {}
                 _ => return Ok(()),
             }
         }
@@ -164,13 +446,13 @@ impl Grid {
     }
 }
 
-impl fmt::Display for Grid {
+impl<T: Symbol> fmt::Display for Grid<T> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         for i in 0..self.height {
             for j in 0..self.width {
-                match &self.cells[i][j] {
-                    Some(cell) => {
-                        write!(fmt, "{}", cell)?;
+                match &self[(i, j)] {
+                    Some(symbol) => {
+                        write!(fmt, "{}", symbol)?;
                     }
                     None => {
                         write!(fmt, "-")?;
@@ -200,11 +482,17 @@ impl fmt::Display for Cell {
     }
 }
 
-impl ops::Index<(usize, usize)> for Grid {
-    type Output = Option<Cell>;
+impl<T: Symbol> ops::Index<(usize, usize)> for Grid<T> {
+    type Output = Option<T>;
 
     fn index(&self, idx: (usize, usize)) -> &Self::Output {
-        &self.cells[idx.0][idx.1]
+        &self.cells[idx.0 * self.width + idx.1]
+    }
+}
+
+impl<T: Symbol> ops::IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, idx: (usize, usize)) -> &mut Self::Output {
+        &mut self.cells[idx.0 * self.width + idx.1]
     }
 }
 
@@ -218,3 +506,92 @@ impl ops::Not for Cell {
         }
     }
 }
+
+// A small seeded xorshift64* generator, so that `Grid::generate` is
+// deterministic for a given seed without pulling in an external dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn gen_range(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.gen_range(i + 1);
+            slice.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[&str]) -> Grid {
+        Grid::parse(lines.iter().map(|l| Ok::<_, GridError>(*l))).expect("valid grid")
+    }
+
+    #[test]
+    fn solves_a_puzzle_with_a_unique_solution() {
+        let mut grid = parse(&["1 1 - -", "- - 0 -", "1 - - -", "- - - -"]);
+
+        grid.solve().expect("puzzle has a unique solution");
+
+        assert_eq!(grid.to_string(), "1 1 0 0\n0 1 0 1\n1 0 1 0\n0 0 1 1");
+    }
+
+    #[test]
+    fn rejects_an_ambiguous_puzzle() {
+        let mut grid = parse(&["1 - - -", "- - 0 -", "- 1 - -", "- - - 0"]);
+
+        assert!(matches!(grid.solve(), Err(GridError::AmbiguousPuzzle)));
+    }
+
+    #[test]
+    fn rejects_an_unsolvable_puzzle() {
+        // The first two rows are forced by propagation alone to the same
+        // value, which no completion of the remaining rows can fix.
+        let mut grid = parse(&["1 1 0 -", "1 1 0 -", "- - - -", "- - - -"]);
+
+        assert!(matches!(grid.solve(), Err(GridError::Unsolvable)));
+    }
+
+    #[test]
+    fn generates_a_grid_with_a_unique_solution() {
+        let grid: Grid = Grid::generate(6, 6, 42).expect("6x6 is generatable");
+
+        assert!(matches!(grid.solutions(), Ok(Solutions::Unique(_))));
+    }
+
+    #[test]
+    fn generate_rejects_odd_dimensions() {
+        let result: Result<Grid, GridError> = Grid::generate(3, 4, 42);
+
+        assert!(matches!(result, Err(GridError::OddDimension)));
+    }
+
+    #[test]
+    fn parse_many_splits_puzzles_on_blank_lines() {
+        let lines = ["1 1 - -", "- - 0 -", "1 - - -", "- - - -", "", "1 - - -", "- - 0 -", "- 1 - -", "- - - 0"];
+
+        let grids: Vec<Grid> = Grid::parse_many(lines.iter().map(|l| Ok::<_, GridError>(*l))).expect("two valid puzzles");
+
+        assert_eq!(grids.len(), 2);
+        assert_eq!(grids[0].to_string(), "1 1 - -\n- - 0 -\n1 - - -\n- - - -");
+        assert_eq!(grids[1].to_string(), "1 - - -\n- - 0 -\n- 1 - -\n- - - 0");
+    }
+}
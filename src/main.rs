@@ -3,6 +3,7 @@ use std::fs;
 use std::io;
 use std::io::BufRead;
 
+use error::GridError;
 use grid::Grid;
 
 mod error;
@@ -11,12 +12,37 @@ mod grid;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // TODO: refactor this code
     let args = env::args().collect::<Vec<String>>();
+
+    if args.get(1).map(String::as_str) == Some("--generate") {
+        let (Some(width), Some(height), Some(seed)) = (args.get(2), args.get(3), args.get(4)) else {
+            return Err("usage: binero_solver --generate WIDTH HEIGHT SEED".into());
+        };
+        let width: usize = width.parse()?;
+        let height: usize = height.parse()?;
+        let seed: u64 = seed.parse()?;
+
+        let grid: Grid = Grid::generate(width, height, seed)?;
+        println!("{}", grid);
+
+        return Ok(());
+    }
+
     let file = fs::File::open(&args[1])?;
     let reader = io::BufReader::new(file);
 
-    let mut grid = Grid::parse(reader.lines())?;
-    println!("{}", grid);
-    grid.solve()?;
+    let grids: Vec<Grid> = Grid::parse_many(reader.lines())?;
+
+    for (i, mut grid) in grids.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+
+        match grid.solve() {
+            Ok(()) => println!("{}", grid),
+            Err(GridError::AmbiguousPuzzle) => println!("puzzle is under-constrained: multiple solutions exist"),
+            Err(err) => return Err(err.into()),
+        }
+    }
 
     Ok(())
 }
\ No newline at end of file